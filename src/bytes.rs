@@ -0,0 +1,108 @@
+use crate::{encode_with, Error, Result, Ucs2Char};
+
+/// Byte order used by [`encode_bytes`] and [`decode_bytes`] to serialize
+/// UCS-2 code units to or from a byte stream.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ByteOrder {
+    /// Little-endian, as used by UTF-16LE.
+    Little,
+    /// Big-endian, as used by UTF-16BE.
+    Big,
+}
+
+impl ByteOrder {
+    fn encode_unit(self, unit: u16) -> [u8; 2] {
+        match self {
+            Self::Little => unit.to_le_bytes(),
+            Self::Big => unit.to_be_bytes(),
+        }
+    }
+
+    fn decode_unit(self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Self::Little => u16::from_le_bytes(bytes),
+            Self::Big => u16::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// The byte-order mark, U+FEFF.
+const BOM: u16 = 0xFEFF;
+
+/// Encodes an input UTF-8 string into a UCS-2 byte stream, serializing each
+/// code unit as two bytes in `byte_order`, optionally preceded by a U+FEFF
+/// byte-order mark.
+///
+/// The returned `usize` represents the length of the returned buffer, in
+/// bytes.
+pub fn encode_bytes(
+    input: &str,
+    output: &mut [u8],
+    byte_order: ByteOrder,
+    bom: bool,
+) -> Result<usize> {
+    let buffer_size = output.len();
+    let mut i = 0;
+
+    let mut push = |unit: u16| -> Result<()> {
+        if i + 1 >= buffer_size {
+            return Err(Error::BufferOverflow);
+        }
+
+        let bytes = byte_order.encode_unit(unit);
+        output[i] = bytes[0];
+        output[i + 1] = bytes[1];
+        i += 2;
+
+        Ok(())
+    };
+
+    if bom {
+        push(BOM)?;
+    }
+
+    encode_with(input, &mut push)?;
+
+    Ok(i)
+}
+
+/// Decodes a UCS-2 byte stream into UTF-8, reading each code unit as two
+/// bytes in `byte_order`. A leading U+FEFF byte-order mark, if present, is
+/// consumed and not included in the output. A trailing odd byte, if any,
+/// is ignored.
+///
+/// The returned `usize` represents the length of the returned buffer, in
+/// bytes.
+pub fn decode_bytes(input: &[u8], byte_order: ByteOrder, output: &mut [u8]) -> Result<usize> {
+    let buffer_size = output.len();
+    let mut i = 0;
+    let mut chunks = input.chunks_exact(2);
+
+    if let Some(chunk) = chunks.next() {
+        let unit = byte_order.decode_unit([chunk[0], chunk[1]]);
+        if unit != BOM {
+            i = write_utf8(output, i, buffer_size, unit)?;
+        }
+    }
+
+    for chunk in chunks {
+        let unit = byte_order.decode_unit([chunk[0], chunk[1]]);
+        i = write_utf8(output, i, buffer_size, unit)?;
+    }
+
+    Ok(i)
+}
+
+/// Writes the UTF-8 encoding of `unit` to `output[i..]`, returning the new
+/// write offset.
+fn write_utf8(output: &mut [u8], i: usize, buffer_size: usize, unit: u16) -> Result<usize> {
+    let (len, bytes) = Ucs2Char::new_unchecked(unit).to_utf8();
+
+    if i + len > buffer_size {
+        return Err(Error::BufferOverflow);
+    }
+
+    output[i..i + len].copy_from_slice(&bytes[..len]);
+
+    Ok(i + len)
+}
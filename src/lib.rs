@@ -4,15 +4,24 @@
 #![deny(missing_docs)]
 #![deny(clippy::all)]
 
+mod bytes;
 mod macros;
+mod ucs2_char;
+mod utf16;
+mod utf8_decode;
+mod utf8_encode;
 
 /// These need to be public for the `ucs2_cstr!` macro, but are not
 /// intended to be called directly.
 #[doc(hidden)]
 pub use macros::{str_num_ucs2_chars, str_to_ucs2};
 
-use bit_field::BitField;
+pub use bytes::{decode_bytes, encode_bytes, ByteOrder};
+pub use ucs2_char::Ucs2Char;
+pub use utf16::{decode_utf16, encode_utf16};
+
 use core::fmt::{self, Display, Formatter};
+use utf8_decode::decode_one;
 
 /// Possible errors returned by the API.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -21,6 +30,11 @@ pub enum Error {
     BufferOverflow,
     /// Input contained a character which cannot be represented in UCS-2.
     MultiByte,
+    /// Input contained a high surrogate not followed by a low surrogate,
+    /// or a low surrogate not preceded by a high surrogate.
+    UnpairedSurrogate,
+    /// Input was not valid UTF-8.
+    InvalidUtf8,
 }
 
 impl Display for Error {
@@ -30,81 +44,108 @@ impl Display for Error {
             Self::MultiByte => {
                 f.write_str("input contains a character which cannot be represented in UCS-2")
             }
+            Self::UnpairedSurrogate => f.write_str("input contains an unpaired surrogate"),
+            Self::InvalidUtf8 => f.write_str("input is not valid UTF-8"),
         }
     }
 }
 
 type Result<T> = core::result::Result<T, Error>;
 
-/// Value returned by `ucs2_from_utf8_at_offset`.
-struct Ucs2CharFromUtf8 {
-    /// UCS-2 character.
-    val: u16,
-    /// Number of bytes needed to encode the character in UTF-8.
-    num_bytes: u8,
+/// Encodes an input UTF-8 string into a UCS-2 string.
+///
+/// The returned `usize` represents the length of the returned buffer,
+/// measured in 2-byte characters.
+pub fn encode(input: &str, buffer: &mut [u16]) -> Result<usize> {
+    let buffer_size = buffer.len();
+    let mut i = 0;
+
+    encode_with(input, |ch| {
+        if i >= buffer_size {
+            Err(Error::BufferOverflow)
+        } else {
+            buffer[i] = ch;
+            i += 1;
+            Ok(())
+        }
+    })?;
+
+    Ok(i)
+}
+
+/// Encode UTF-8 string to UCS-2 with a custom callback function.
+///
+/// `output` is a function which receives every encoded character.
+pub fn encode_with<F>(input: &str, output: F) -> Result<()>
+where
+    F: FnMut(u16) -> Result<()>,
+{
+    encode_utf8_bytes_with(input.as_bytes(), output)
 }
 
-/// Get a UCS-2 character from a UTF-8 byte slice at the given offset.
+/// Encodes UTF-8 bytes which have not already been validated into a UCS-2
+/// string.
 ///
-/// # Safety
+/// This is the byte-oriented counterpart to [`encode`]: where `encode`
+/// takes a `&str` and can therefore assume its input is well-formed,
+/// `encode_from_bytes` accepts untrusted bytes and reports
+/// `Error::InvalidUtf8` if they are not valid UTF-8.
 ///
-/// The input `bytes` must be valid UTF-8.
-const unsafe fn ucs2_from_utf8_at_offset(bytes: &[u8], offset: usize) -> Result<Ucs2CharFromUtf8> {
-    let len = bytes.len();
-    let ch;
-    let ch_len;
-
-    if bytes[offset] & 0b1000_0000 == 0b0000_0000 {
-        ch = bytes[offset] as u16;
-        ch_len = 1;
-    } else if bytes[offset] & 0b1110_0000 == 0b1100_0000 {
-        // 2 byte codepoint
-        if offset + 1 >= len {
-            // safe: len is the length of bytes,
-            // and bytes is a direct view into the
-            // buffer of input, which in order to be a valid
-            // utf-8 string _must_ contain `i + 1`.
-            unsafe { core::hint::unreachable_unchecked() }
-        }
+/// The returned `usize` represents the length of the returned buffer,
+/// measured in 2-byte characters.
+pub fn encode_from_bytes(input: &[u8], buffer: &mut [u16]) -> Result<usize> {
+    let buffer_size = buffer.len();
+    let mut i = 0;
 
-        let a = (bytes[offset] & 0b0001_1111) as u16;
-        let b = (bytes[offset + 1] & 0b0011_1111) as u16;
-        ch = a << 6 | b;
-        ch_len = 2;
-    } else if bytes[offset] & 0b1111_0000 == 0b1110_0000 {
-        // 3 byte codepoint
-        if offset + 2 >= len || offset + 1 >= len {
-            // safe: impossible utf-8 string.
-            unsafe { core::hint::unreachable_unchecked() }
+    encode_utf8_bytes_with(input, |ch| {
+        if i >= buffer_size {
+            Err(Error::BufferOverflow)
+        } else {
+            buffer[i] = ch;
+            i += 1;
+            Ok(())
         }
+    })?;
 
-        let a = (bytes[offset] & 0b0000_1111) as u16;
-        let b = (bytes[offset + 1] & 0b0011_1111) as u16;
-        let c = (bytes[offset + 2] & 0b0011_1111) as u16;
-        ch = a << 12 | b << 6 | c;
-        ch_len = 3;
-    } else if bytes[offset] & 0b1111_0000 == 0b1111_0000 {
-        return Err(Error::MultiByte); // UTF-16
-    } else {
-        // safe: impossible utf-8 string.
-        unsafe { core::hint::unreachable_unchecked() }
-    }
+    Ok(i)
+}
 
-    Ok(Ucs2CharFromUtf8 {
-        val: ch,
-        num_bytes: ch_len,
-    })
+/// Shared implementation behind [`encode_with`] and [`encode_from_bytes`].
+fn encode_utf8_bytes_with<F>(bytes: &[u8], mut output: F) -> Result<()>
+where
+    F: FnMut(u16) -> Result<()>,
+{
+    let len = bytes.len();
+    let mut i = 0;
+
+    while i < len {
+        let ch = decode_one(bytes, i)?;
+        i += usize::from(ch.num_bytes);
+        output(ch.val.as_u16())?;
+    }
+    Ok(())
 }
 
-/// Encodes an input UTF-8 string into a UCS-2 string.
+/// Default replacement character substituted by [`encode_lossy`] and
+/// [`encode_lossy_with`] for scalars which cannot be represented in UCS-2.
+///
+/// This is the Unicode replacement character U+FFFD, the same default used
+/// by `encoding_rs` and `char::decode_utf16`. UEFI callers that would
+/// rather fall back to `'?'` can pass `0x003F` instead.
+pub const REPLACEMENT_CHARACTER: u16 = 0xFFFD;
+
+/// Encodes an input UTF-8 string into a UCS-2 string, substituting
+/// `replacement` for every character which cannot be represented in UCS-2
+/// instead of failing with `Error::MultiByte`.
 ///
 /// The returned `usize` represents the length of the returned buffer,
-/// measured in 2-byte characters.
-pub fn encode(input: &str, buffer: &mut [u16]) -> Result<usize> {
+/// measured in 2-byte characters. Unlike [`encode`], the only error this
+/// can still return is `Error::BufferOverflow`.
+pub fn encode_lossy(input: &str, buffer: &mut [u16], replacement: u16) -> Result<usize> {
     let buffer_size = buffer.len();
     let mut i = 0;
 
-    encode_with(input, |ch| {
+    encode_lossy_with(input, replacement, |ch| {
         if i >= buffer_size {
             Err(Error::BufferOverflow)
         } else {
@@ -117,10 +158,13 @@ pub fn encode(input: &str, buffer: &mut [u16]) -> Result<usize> {
     Ok(i)
 }
 
-/// Encode UTF-8 string to UCS-2 with a custom callback function.
+/// Encode UTF-8 string to UCS-2 with a custom callback function,
+/// substituting `replacement` for every character which cannot be
+/// represented in UCS-2.
 ///
-/// `output` is a function which receives every encoded character.
-pub fn encode_with<F>(input: &str, mut output: F) -> Result<()>
+/// `output` is a function which receives every encoded character. Unlike
+/// [`encode_with`], this can only fail if `output` itself returns an error.
+pub fn encode_lossy_with<F>(input: &str, replacement: u16, mut output: F) -> Result<()>
 where
     F: FnMut(u16) -> Result<()>,
 {
@@ -129,10 +173,19 @@ where
     let mut i = 0;
 
     while i < len {
-        // SAFETY: `bytes` is valid UTF-8.
-        let ch = unsafe { ucs2_from_utf8_at_offset(bytes, i) }?;
-        i += usize::from(ch.num_bytes);
-        output(ch.val)?;
+        match decode_one(bytes, i) {
+            Ok(ch) => {
+                i += usize::from(ch.num_bytes);
+                output(ch.val.as_u16())?;
+            }
+            Err(Error::MultiByte) => {
+                // An astral scalar is always 4 bytes of UTF-8; skip it and
+                // emit the replacement code unit in its place.
+                i += 4;
+                output(replacement)?;
+            }
+            Err(err) => return Err(err),
+        }
     }
     Ok(())
 }
@@ -149,33 +202,102 @@ where
     let mut written = 0;
 
     for ch in input.iter() {
-        /*
-         * We need to find how many bytes of UTF-8 this UCS-2 code-point needs. Because UCS-2 can only encode
-         * the Basic Multilingual Plane, a maximum of three bytes are needed.
-         */
-        if (0x000..0x0080).contains(ch) {
-            output(&[*ch as u8])?;
+        // Because UCS-2 can only encode the Basic Multilingual Plane, a
+        // maximum of three bytes of UTF-8 are needed per code unit.
+        let (len, bytes) = Ucs2Char::new_unchecked(*ch).to_utf8();
+        output(&bytes[..len])?;
+        written += len;
+    }
 
-            written += 1;
-        } else if (0x0080..0x0800).contains(ch) {
-            let first = 0b1100_0000 + ch.get_bits(6..11) as u8;
-            let last = 0b1000_0000 + ch.get_bits(0..6) as u8;
+    Ok(written)
+}
 
-            output(&[first, last])?;
+/// Returns an iterator that lazily encodes `input` as UCS-2.
+///
+/// This is an alternative to [`encode`] for callers who do not want to
+/// pre-size an output buffer: the iterator can be `collect`ed, `take`n, or
+/// fed directly into another adapter. Each item is a `u16` code unit, or an
+/// `Err` if `input` contains a character which cannot be represented in
+/// UCS-2.
+pub fn encode_iter(input: &str) -> EncodeUcs2<'_> {
+    EncodeUcs2 {
+        bytes: input.as_bytes(),
+        offset: 0,
+    }
+}
 
-            written += 2;
-        } else {
-            let first = 0b1110_0000 + ch.get_bits(12..16) as u8;
-            let mid = 0b1000_0000 + ch.get_bits(6..12) as u8;
-            let last = 0b1000_0000 + ch.get_bits(0..6) as u8;
+/// Iterator over the UCS-2 code units of a string, returned by
+/// [`encode_iter`].
+pub struct EncodeUcs2<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
 
-            output(&[first, mid, last])?;
+impl<'a> Iterator for EncodeUcs2<'a> {
+    type Item = Result<u16>;
 
-            written += 3;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.bytes.len() {
+            return None;
+        }
+
+        match decode_one(self.bytes, self.offset) {
+            Ok(ch) => {
+                self.offset += usize::from(ch.num_bytes);
+                Some(Ok(ch.val.as_u16()))
+            }
+            Err(err) => {
+                // Stop iterating once an unrepresentable character is hit,
+                // rather than risk reinterpreting a multi-byte character's
+                // continuation bytes as a new character.
+                self.offset = self.bytes.len();
+                Some(Err(err))
+            }
         }
     }
+}
 
-    Ok(written)
+/// Returns an iterator that lazily decodes `input` from UCS-2 to UTF-8.
+///
+/// This is an alternative to [`decode`] for callers who do not want to
+/// pre-size an output buffer. Each item is a single decoded UTF-8 byte; a
+/// UCS-2 code unit can yield up to three of them.
+pub fn decode_iter(input: &[u16]) -> DecodeUcs2<'_> {
+    DecodeUcs2 {
+        input: input.iter(),
+        buf: [0; 3],
+        buf_len: 0,
+        buf_pos: 0,
+    }
+}
+
+/// Iterator over the UTF-8 bytes decoded from a UCS-2 string, returned by
+/// [`decode_iter`].
+pub struct DecodeUcs2<'a> {
+    input: core::slice::Iter<'a, u16>,
+    /// UTF-8 bytes of the code unit currently being decoded.
+    buf: [u8; 3],
+    buf_len: u8,
+    buf_pos: u8,
+}
+
+impl<'a> Iterator for DecodeUcs2<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        while self.buf_pos >= self.buf_len {
+            let ch = *self.input.next()?;
+            let (len, bytes) = Ucs2Char::new_unchecked(ch).to_utf8();
+
+            self.buf = bytes;
+            self.buf_len = len as u8;
+            self.buf_pos = 0;
+        }
+
+        let byte = self.buf[self.buf_pos as usize];
+        self.buf_pos += 1;
+        Some(byte)
+    }
 }
 
 /// Decode an input UCS-2 string into a UTF-8 string.
@@ -1,4 +1,5 @@
-use crate::{ucs2_from_utf8_at_offset, Error};
+use crate::utf8_decode::decode_one;
+use crate::Error;
 
 /// Count the number of UCS-2 characters in a string. Return an error if
 /// the string cannot be encoded in UCS-2.
@@ -10,8 +11,7 @@ pub const fn str_num_ucs2_chars(s: &str) -> Result<usize, Error> {
     let mut num_ucs2_chars = 0;
 
     while offset < len {
-        // SAFETY: `bytes` is valid UTF-8.
-        match unsafe { ucs2_from_utf8_at_offset(bytes, offset) } {
+        match decode_one(bytes, offset) {
             Ok(ch) => {
                 offset += ch.num_bytes as usize;
                 num_ucs2_chars += 1;
@@ -35,13 +35,12 @@ pub const fn str_to_ucs2<const N: usize>(s: &str) -> Result<[u16; N], Error> {
     let mut output_offset = 0;
     let mut input_offset = 0;
     while input_offset < len {
-        // SAFETY: `bytes` is valid UTF-8.
-        match unsafe { ucs2_from_utf8_at_offset(bytes, input_offset) } {
+        match decode_one(bytes, input_offset) {
             Ok(ch) => {
-                if ch.val == 0 {
+                if ch.val.as_u16() == 0 {
                     panic!("interior null character");
                 } else {
-                    output[output_offset] = ch.val;
+                    output[output_offset] = ch.val.as_u16();
                     output_offset += 1;
                     input_offset += ch.num_bytes as usize;
                 }
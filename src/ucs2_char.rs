@@ -0,0 +1,77 @@
+use crate::utf8_encode::scalar_to_utf8;
+use crate::{Error, Result};
+use core::convert::TryFrom;
+
+/// A single UCS-2 code unit.
+///
+/// This mirrors the `Utf8Char`/`Utf16Char` wrapper types from the
+/// `encode_unicode` crate: a `Ucs2Char` is a `u16` guaranteed to represent a
+/// scalar value in the Basic Multilingual Plane, and guaranteed not to be a
+/// lone surrogate (`0xD800..=0xDFFF`). This lets callers store and pass
+/// around a code unit with that invariant encoded in the type, rather than
+/// juggling a bare `u16` which might be a surrogate or part of an astral
+/// character.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Ucs2Char(u16);
+
+impl Ucs2Char {
+    /// Creates a `Ucs2Char` from a `u16` without checking that it is not a
+    /// lone surrogate.
+    pub(crate) const fn new_unchecked(val: u16) -> Self {
+        Self(val)
+    }
+
+    /// Converts a `char` to a `Ucs2Char`.
+    ///
+    /// Returns `Error::MultiByte` if `c` is outside the Basic Multilingual
+    /// Plane.
+    pub const fn from_char(c: char) -> Result<Self> {
+        if c as u32 > 0xFFFF {
+            Err(Error::MultiByte)
+        } else {
+            // `char` can never hold a surrogate, so `c` is always a valid
+            // `Ucs2Char` at this point.
+            Ok(Self(c as u16))
+        }
+    }
+
+    /// Returns the UCS-2 code unit as a `u16`.
+    pub const fn as_u16(self) -> u16 {
+        self.0
+    }
+
+    /// Encodes this code unit as UTF-8.
+    ///
+    /// Returns the number of bytes written, and a buffer holding them.
+    /// Only the first `n` bytes of the buffer, where `n` is the returned
+    /// count, are meaningful.
+    pub fn to_utf8(self) -> (usize, [u8; 3]) {
+        // A `Ucs2Char` is confined to the BMP, so this never needs the
+        // fourth byte `scalar_to_utf8` can produce for astral scalars.
+        let (len, bytes) = scalar_to_utf8(u32::from(self.0));
+        (len, [bytes[0], bytes[1], bytes[2]])
+    }
+}
+
+impl TryFrom<u16> for Ucs2Char {
+    type Error = Error;
+
+    /// Returns `Error::UnpairedSurrogate` if `val` is a lone surrogate
+    /// (`0xD800..=0xDFFF`), as it cannot stand on its own as a UCS-2
+    /// character.
+    fn try_from(val: u16) -> Result<Self> {
+        if (0xD800..=0xDFFF).contains(&val) {
+            Err(Error::UnpairedSurrogate)
+        } else {
+            Ok(Self(val))
+        }
+    }
+}
+
+impl From<Ucs2Char> for char {
+    fn from(ch: Ucs2Char) -> Self {
+        // `Ucs2Char` never holds a surrogate, so this is always a valid
+        // scalar value.
+        char::from_u32(u32::from(ch.0)).expect("Ucs2Char must hold a valid scalar value")
+    }
+}
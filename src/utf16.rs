@@ -0,0 +1,75 @@
+use crate::utf8_encode::scalar_to_utf8;
+use crate::{Error, Result};
+
+/// Encodes an input UTF-8 string into UTF-16 code units.
+///
+/// Unlike [`encode`](crate::encode), this represents characters outside the
+/// Basic Multilingual Plane as surrogate pairs instead of failing with
+/// `Error::MultiByte`, so it can losslessly round-trip any valid `str`.
+///
+/// The returned `usize` represents the length of the returned buffer,
+/// measured in 2-byte code units.
+pub fn encode_utf16(input: &str, buffer: &mut [u16]) -> Result<usize> {
+    let buffer_size = buffer.len();
+    let mut i = 0;
+
+    for c in input.chars() {
+        let c = c as u32;
+
+        if c < 0x10000 {
+            if i >= buffer_size {
+                return Err(Error::BufferOverflow);
+            }
+            buffer[i] = c as u16;
+            i += 1;
+        } else {
+            if i + 1 >= buffer_size {
+                return Err(Error::BufferOverflow);
+            }
+            let v = c - 0x10000;
+            buffer[i] = 0xD800 | (v >> 10) as u16;
+            buffer[i + 1] = 0xDC00 | (v & 0x3FF) as u16;
+            i += 2;
+        }
+    }
+
+    Ok(i)
+}
+
+/// Decodes an input UTF-16 string into UTF-8.
+///
+/// Unlike [`decode`](crate::decode), this supports the full Unicode range:
+/// a surrogate pair is combined into its astral scalar value and emitted as
+/// four bytes of UTF-8. A high surrogate not followed by a valid low
+/// surrogate, or a lone low surrogate, is reported as
+/// `Error::UnpairedSurrogate`.
+pub fn decode_utf16(input: &[u16], output: &mut [u8]) -> Result<usize> {
+    let buffer_size = output.len();
+    let mut i = 0;
+    let mut units = input.iter();
+
+    while let Some(&unit) = units.next() {
+        let scalar = if (0xD800..0xDC00).contains(&unit) {
+            let lo = *units.next().ok_or(Error::UnpairedSurrogate)?;
+            if !(0xDC00..0xE000).contains(&lo) {
+                return Err(Error::UnpairedSurrogate);
+            }
+
+            0x10000 + ((u32::from(unit) & 0x3FF) << 10) + (u32::from(lo) & 0x3FF)
+        } else if (0xDC00..0xE000).contains(&unit) {
+            return Err(Error::UnpairedSurrogate);
+        } else {
+            u32::from(unit)
+        };
+
+        let (len, bytes) = scalar_to_utf8(scalar);
+
+        if i + len > buffer_size {
+            return Err(Error::BufferOverflow);
+        }
+        output[i..i + len].copy_from_slice(&bytes[..len]);
+        i += len;
+    }
+
+    Ok(i)
+}
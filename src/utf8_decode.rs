@@ -0,0 +1,94 @@
+//! Branchless table-driven UTF-8 decoder.
+//!
+//! This is Bjorn Hoehrmann's DFA-based decoder (the same one used by
+//! `bstr`): a 256-entry table maps each byte to a character class, and a
+//! transition table indexed by `state + class` yields the next state.
+//! State `0` is the accept state and state `12` is a dedicated reject
+//! state that traps malformed sequences, so decoding never has to assume
+//! its input is well-formed.
+
+use crate::{Error, Result, Ucs2Char};
+
+const ACCEPT: u8 = 0;
+const REJECT: u8 = 12;
+
+#[rustfmt::skip]
+const CLASSES: [u8; 256] = [
+    0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0, 0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+    0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0, 0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+    0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0, 0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+    0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0, 0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,
+    1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1, 9,9,9,9,9,9,9,9,9,9,9,9,9,9,9,9,
+    7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7, 7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,7,
+    8,8,2,2,2,2,2,2,2,2,2,2,2,2,2,2, 2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,2,
+    10,3,3,3,3,3,3,3,3,3,3,3,3,4,3,3, 11,6,6,6,5,8,8,8,8,8,8,8,8,8,8,8,
+];
+
+#[rustfmt::skip]
+const TRANSITIONS: [u8; 108] = [
+    0,12,24,36,60,96,84,12,12,12,48,72,
+    12,12,12,12,12,12,12,12,12,12,12,12,
+    12, 0,12,12,12,12,12, 0,12, 0,12,12,
+    12,24,12,12,12,12,12,24,12,24,12,12,
+    12,12,12,12,12,12,12,24,12,12,12,12,
+    12,24,12,12,12,12,12,12,12,24,12,12,
+    12,12,12,12,12,12,12,36,12,36,12,12,
+    12,36,12,12,12,12,12,36,12,36,12,12,
+    12,36,12,12,12,12,12,12,12,12,12,12,
+];
+
+/// Value returned by `decode_one`.
+pub(crate) struct Ucs2CharFromUtf8 {
+    /// UCS-2 character.
+    pub(crate) val: Ucs2Char,
+    /// Number of bytes the character took up in UTF-8.
+    pub(crate) num_bytes: u8,
+}
+
+/// Decodes a single scalar value from `bytes` at `offset`.
+///
+/// Unlike the masking logic this replaces, `bytes` does not need to be
+/// pre-validated as UTF-8: malformed sequences are reported as
+/// `Error::InvalidUtf8` instead of triggering undefined behavior. A
+/// well-formed scalar outside the Basic Multilingual Plane is reported as
+/// `Error::MultiByte`, same as before.
+pub(crate) const fn decode_one(bytes: &[u8], offset: usize) -> Result<Ucs2CharFromUtf8> {
+    let len = bytes.len();
+    let mut state = ACCEPT;
+    let mut codepoint: u32 = 0;
+    let mut i = offset;
+
+    loop {
+        if i >= len {
+            return Err(Error::InvalidUtf8);
+        }
+
+        let byte = bytes[i];
+        let class = CLASSES[byte as usize];
+
+        codepoint = if state == ACCEPT {
+            (0xFFu32 >> class) & (byte as u32)
+        } else {
+            (byte as u32 & 0x3F) | (codepoint << 6)
+        };
+
+        state = TRANSITIONS[(state + class) as usize];
+        i += 1;
+
+        if state == ACCEPT {
+            break;
+        }
+        if state == REJECT {
+            return Err(Error::InvalidUtf8);
+        }
+    }
+
+    if codepoint > 0xFFFF {
+        return Err(Error::MultiByte);
+    }
+
+    Ok(Ucs2CharFromUtf8 {
+        val: Ucs2Char::new_unchecked(codepoint as u16),
+        num_bytes: (i - offset) as u8,
+    })
+}
@@ -0,0 +1,32 @@
+use bit_field::BitField;
+
+/// Encodes a scalar value as UTF-8.
+///
+/// Returns the number of bytes written, and a buffer holding them. Only
+/// the first `n` bytes of the buffer, where `n` is the returned count, are
+/// meaningful.
+///
+/// Shared by [`crate::Ucs2Char::to_utf8`] (which only ever needs the first
+/// three bytes, since a `Ucs2Char` is confined to the Basic Multilingual
+/// Plane) and [`crate::decode_utf16`] (which can produce all four, for an
+/// astral scalar decoded from a surrogate pair).
+pub(crate) fn scalar_to_utf8(scalar: u32) -> (usize, [u8; 4]) {
+    if scalar < 0x80 {
+        (1, [scalar as u8, 0, 0, 0])
+    } else if scalar < 0x800 {
+        let first = 0b1100_0000 + scalar.get_bits(6..11) as u8;
+        let last = 0b1000_0000 + scalar.get_bits(0..6) as u8;
+        (2, [first, last, 0, 0])
+    } else if scalar < 0x10000 {
+        let first = 0b1110_0000 + scalar.get_bits(12..16) as u8;
+        let mid = 0b1000_0000 + scalar.get_bits(6..12) as u8;
+        let last = 0b1000_0000 + scalar.get_bits(0..6) as u8;
+        (3, [first, mid, last, 0])
+    } else {
+        let first = 0b1111_0000 + scalar.get_bits(18..21) as u8;
+        let mid1 = 0b1000_0000 + scalar.get_bits(12..18) as u8;
+        let mid2 = 0b1000_0000 + scalar.get_bits(6..12) as u8;
+        let last = 0b1000_0000 + scalar.get_bits(0..6) as u8;
+        (4, [first, mid1, mid2, last])
+    }
+}
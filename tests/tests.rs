@@ -1,4 +1,9 @@
-use ucs2::{decode, decode_with, encode, Error};
+use core::convert::TryFrom;
+use ucs2::{
+    decode, decode_bytes, decode_iter, decode_utf16, decode_with, encode, encode_bytes,
+    encode_from_bytes, encode_iter, encode_lossy, encode_lossy_with, encode_utf16, ByteOrder,
+    Error, Ucs2Char, REPLACEMENT_CHARACTER,
+};
 
 #[test]
 fn encoding() {
@@ -41,6 +46,187 @@ fn decoding() {
     );
 }
 
+#[test]
+fn encoding_iter() {
+    let input = "őэ╋";
+
+    let result: Result<Vec<u16>, Error> = encode_iter(input).collect();
+    assert_eq!(result, Ok(vec![0x0151, 0x044D, 0x254B]));
+
+    let input = "a😎b";
+    let mut iter = encode_iter(input);
+    assert_eq!(iter.next(), Some(Ok(0x0061)));
+    assert_eq!(iter.next(), Some(Err(Error::MultiByte)));
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn decoding_iter() {
+    let input = "$¢ह한";
+    let mut u16_buffer = [0u16; 4];
+    assert_eq!(encode(input, &mut u16_buffer), Ok(4));
+
+    let decoded: Vec<u8> = decode_iter(&u16_buffer).collect();
+    assert_eq!(core::str::from_utf8(&decoded), Ok(input));
+}
+
+#[test]
+fn ucs2_char() {
+    let ch = Ucs2Char::from_char('한').unwrap();
+    assert_eq!(ch.as_u16(), 0xD55C);
+    assert_eq!(char::from(ch), '한');
+    assert_eq!(ch.to_utf8(), (3, [0xED, 0x95, 0x9C]));
+
+    assert_eq!(Ucs2Char::from_char('😎'), Err(Error::MultiByte));
+
+    assert_eq!(Ucs2Char::try_from(0x0041).unwrap().as_u16(), 0x0041);
+    assert_eq!(Ucs2Char::try_from(0xD800), Err(Error::UnpairedSurrogate));
+}
+
+#[test]
+fn encoding_lossy() {
+    let input = "a😎b";
+    let mut buffer = [0u16; 3];
+
+    assert_eq!(
+        encode_lossy(input, &mut buffer, REPLACEMENT_CHARACTER),
+        Ok(3)
+    );
+    assert_eq!(buffer[..], [0x0061, REPLACEMENT_CHARACTER, 0x0062]);
+
+    // A custom replacement, e.g. for UEFI callers that prefer '?'.
+    assert_eq!(encode_lossy(input, &mut buffer, 0x003F), Ok(3));
+    assert_eq!(buffer[..], [0x0061, 0x003F, 0x0062]);
+
+    let mut buffer = [0u16; 2];
+    assert_eq!(
+        encode_lossy(input, &mut buffer, REPLACEMENT_CHARACTER),
+        Err(Error::BufferOverflow)
+    );
+}
+
+#[test]
+fn encoding_lossy_with() {
+    let input = "a😎b";
+    let mut seen = Vec::new();
+
+    encode_lossy_with(input, REPLACEMENT_CHARACTER, |ch| {
+        seen.push(ch);
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(seen, [0x0061, REPLACEMENT_CHARACTER, 0x0062]);
+}
+
+#[test]
+fn encoding_utf16() {
+    let input = "a😎b";
+    let mut buffer = [0u16; 4];
+
+    assert_eq!(encode_utf16(input, &mut buffer), Ok(4));
+    assert_eq!(buffer[..], [0x0061, 0xD83D, 0xDE0E, 0x0062]);
+
+    let mut buffer = [0u16; 3];
+    assert_eq!(encode_utf16(input, &mut buffer), Err(Error::BufferOverflow));
+}
+
+#[test]
+fn decoding_utf16_round_trip() {
+    let input = "a😎b";
+    let mut u16_buffer = [0u16; 4];
+    assert_eq!(encode_utf16(input, &mut u16_buffer), Ok(4));
+
+    let mut u8_buffer = [0u8; 6];
+    assert_eq!(decode_utf16(&u16_buffer, &mut u8_buffer), Ok(6));
+    assert_eq!(core::str::from_utf8(&u8_buffer), Ok(input));
+}
+
+#[test]
+fn decoding_utf16_unpaired_surrogate() {
+    let mut u8_buffer = [0u8; 8];
+
+    // Lone high surrogate.
+    assert_eq!(
+        decode_utf16(&[0xD83D], &mut u8_buffer),
+        Err(Error::UnpairedSurrogate)
+    );
+
+    // Lone low surrogate.
+    assert_eq!(
+        decode_utf16(&[0xDE0E], &mut u8_buffer),
+        Err(Error::UnpairedSurrogate)
+    );
+
+    // High surrogate followed by a non-surrogate.
+    assert_eq!(
+        decode_utf16(&[0xD83D, 0x0061], &mut u8_buffer),
+        Err(Error::UnpairedSurrogate)
+    );
+}
+
+#[test]
+fn encoding_bytes() {
+    let input = "ab";
+    let mut buffer = [0u8; 6];
+
+    assert_eq!(
+        encode_bytes(input, &mut buffer, ByteOrder::Little, true),
+        Ok(6)
+    );
+    assert_eq!(buffer, [0xFF, 0xFE, 0x61, 0x00, 0x62, 0x00]);
+
+    assert_eq!(
+        encode_bytes(input, &mut buffer, ByteOrder::Big, false),
+        Ok(4)
+    );
+    assert_eq!(buffer[..4], [0x00, 0x61, 0x00, 0x62]);
+
+    let mut buffer = [0u8; 3];
+    assert_eq!(
+        encode_bytes(input, &mut buffer, ByteOrder::Little, false),
+        Err(Error::BufferOverflow)
+    );
+}
+
+#[test]
+fn decoding_bytes() {
+    let mut output = [0u8; 4];
+
+    // Little-endian with a BOM.
+    let input = [0xFF, 0xFE, 0x61, 0x00, 0x62, 0x00];
+    assert_eq!(decode_bytes(&input, ByteOrder::Little, &mut output), Ok(2));
+    assert_eq!(&output[..2], b"ab");
+
+    // Big-endian without a BOM.
+    let input = [0x00, 0x61, 0x00, 0x62];
+    assert_eq!(decode_bytes(&input, ByteOrder::Big, &mut output), Ok(2));
+    assert_eq!(&output[..2], b"ab");
+}
+
+#[test]
+fn encoding_from_bytes() {
+    let input = "$¢ह한".as_bytes();
+    let mut buffer = [0u16; 4];
+
+    assert_eq!(encode_from_bytes(input, &mut buffer), Ok(4));
+    assert_eq!(buffer[..], [0x0024, 0x00A2, 0x0939, 0xD55C]);
+
+    // Malformed UTF-8 (an overlong encoding of NUL).
+    let invalid = [0xC0, 0x80];
+    assert_eq!(
+        encode_from_bytes(&invalid, &mut buffer),
+        Err(Error::InvalidUtf8)
+    );
+
+    // A truncated multi-byte sequence.
+    let truncated = [0xE2, 0x82];
+    assert_eq!(
+        encode_from_bytes(&truncated, &mut buffer),
+        Err(Error::InvalidUtf8)
+    );
+}
+
 #[test]
 fn decoding_with() {
     let input = "$¢ह한";